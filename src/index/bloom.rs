@@ -1,27 +1,148 @@
 use std::convert::TryInto;
+use std::io::{self, Read, Seek, Write};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use rand::Rng;
+
+/// Magic bytes written at the start of a serialized [`BloomCache`], used
+/// by [`BloomCache::load_from`] to sanity-check the stream before reading
+/// any segments.
+const MAGIC: &[u8; 4] = b"FBLC";
+
+/// Segments with fewer set bits than this are serialized as `Sparse`
+/// records (a list of set-bit positions); segments at or above this
+/// popcount are serialized as `Dense` records (the raw 8192 bytes).
+/// Dense vectors accumulate bits quickly as a build progresses, so this
+/// keeps early, sparse segments cheap to store without paying a
+/// per-segment scan cost on the common dense case.
+const SPARSE_POPCOUNT_THRESHOLD: usize = 1024;
+
+/// Encoding chosen for a single segment's record, based on its popcount
+/// at the time it was serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentEncoding {
+    /// A `u16` count followed by that many sorted `u16` set-bit positions.
+    Sparse,
+    /// The raw 8192-byte bitmap.
+    Dense,
+}
+
+impl SegmentEncoding {
+    fn tag(self) -> u8 {
+        match self {
+            SegmentEncoding::Sparse => 0,
+            SegmentEncoding::Dense => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> io::Result<Self> {
+        match tag {
+            0 => Ok(SegmentEncoding::Sparse),
+            1 => Ok(SegmentEncoding::Dense),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unrecognized segment encoding tag: {other}"),
+            )),
+        }
+    }
+}
+
+/// Compression codec applied to a serialized segment's payload before it
+/// is written to the stream by [`BloomCache::save_to`], independent of
+/// the `Sparse`/`Dense` encoding choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressionType {
+    /// Store the payload as-is.
+    #[default]
+    None,
+    /// Compress the payload with LZ4.
+    Lz4,
+    /// Compress the payload with miniz (DEFLATE).
+    Miniz,
+}
+
+impl CompressionType {
+    fn tag(self) -> u8 {
+        match self {
+            CompressionType::None => 0,
+            CompressionType::Lz4 => 1,
+            CompressionType::Miniz => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> io::Result<Self> {
+        match tag {
+            0 => Ok(CompressionType::None),
+            1 => Ok(CompressionType::Lz4),
+            2 => Ok(CompressionType::Miniz),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unrecognized compression type tag: {other}"),
+            )),
+        }
+    }
+
+    fn compress(self, payload: &[u8]) -> Vec<u8> {
+        match self {
+            CompressionType::None => payload.to_vec(),
+            CompressionType::Lz4 => lz4_flex::compress(payload),
+            CompressionType::Miniz => miniz_oxide::deflate::compress_to_vec(payload, 6),
+        }
+    }
+
+    fn decompress(self, compressed: &[u8], decompressed_len: usize) -> io::Result<Vec<u8>> {
+        match self {
+            CompressionType::None => {
+                if compressed.len() != decompressed_len {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "uncompressed payload length mismatch: expected {decompressed_len}, got {}",
+                            compressed.len()
+                        ),
+                    ));
+                }
+                Ok(compressed.to_vec())
+            }
+            CompressionType::Lz4 => lz4_flex::decompress(compressed, decompressed_len)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            CompressionType::Miniz => {
+                miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, decompressed_len)
+                    .map_err(|err| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("{err:?}"))
+                    })
+            }
+        }
+    }
+}
 
 /// A segment is essentially a bitmap of
 /// representing values in [0, 65536).
 ///
+/// The backing bytes are stored as atomics so that `set` can be called
+/// from multiple threads concurrently: since the filter is monotonic
+/// (bits only ever go from unset to set), a `Relaxed` `fetch_or` can
+/// never lose a bit that another thread set at the same time, and no
+/// CAS loop is required.
 ///
 /// # Examples
 ///
 /// ```
 /// use faiss::index::bloom::Segment;
 ///
-/// let mut segment = Segment::empty();
+/// let segment = Segment::empty();
 /// segment.set(8000u16);
 /// assert!(segment.get(8000u16), "should be able to fetch a value known to exist in the bitmap.");
 /// ```
 #[derive(Debug)]
 pub struct Segment {
-    data: Box<[u8; 8192]>,
+    data: Box<[AtomicU8; 8192]>,
 }
 
 impl Default for Segment {
     fn default() -> Self {
         Self {
-            data: Box::new([0u8; 8192]),
+            data: Box::new([0; 8192].map(AtomicU8::new)),
         }
     }
 }
@@ -37,17 +158,17 @@ impl Segment {
         let byte_offset = (index / 8) as usize;
         let bit_offset = 7 - ((index % 8) as usize);
 
-        let byte = (*self.data)[byte_offset];
+        let byte = self.data[byte_offset].load(Ordering::Relaxed);
         byte & (1 << bit_offset) != 0
     }
 
-    /// Set this value as seen.
+    /// Set this value as seen. Takes `&self`; see the struct-level docs
+    /// for why that's safe to call concurrently.
     #[inline(always)]
-    pub fn set(&mut self, index: u16) {
+    pub fn set(&self, index: u16) {
         let byte_offset = (index / 8) as usize;
         let bit_offset = 7 - ((index % 8) as usize);
-        let byte = &mut (*self.data)[byte_offset];
-        *byte |= 1 << bit_offset;
+        self.data[byte_offset].fetch_or(1 << bit_offset, Ordering::Relaxed);
     }
 
     /// Get an iterator over all the positions that have been
@@ -55,6 +176,40 @@ impl Segment {
     pub fn get_setbits(&self) -> impl Iterator<Item = u16> + use<'_> {
         (0..=65535u16).filter(move |&index| self.get(index))
     }
+
+    /// Count the number of set bits, used to choose a serialization
+    /// encoding in [`BloomCache::save_to`].
+    fn popcount(&self) -> usize {
+        self.data
+            .iter()
+            .map(|byte| byte.load(Ordering::Relaxed).count_ones() as usize)
+            .sum()
+    }
+
+    /// Dump the raw 8192-byte bitmap, for a `Dense` record.
+    fn to_dense_bytes(&self) -> [u8; 8192] {
+        let mut bytes = [0u8; 8192];
+        for (dst, byte) in bytes.iter_mut().zip(self.data.iter()) {
+            *dst = byte.load(Ordering::Relaxed);
+        }
+        bytes
+    }
+
+    /// Rebuild a segment from a `Dense` record's raw bitmap.
+    fn from_dense_bytes(bytes: [u8; 8192]) -> Self {
+        Self {
+            data: Box::new(bytes.map(AtomicU8::new)),
+        }
+    }
+
+    /// Rebuild a segment from a `Sparse` record's set-bit positions.
+    fn from_sparse_positions(positions: &[u16]) -> Self {
+        let segment = Self::empty();
+        for &position in positions {
+            segment.set(position);
+        }
+        segment
+    }
 }
 
 /// A Bloom filter like cache that
@@ -74,7 +229,7 @@ impl Segment {
 /// ```
 /// use faiss::index::bloom::BloomCache;
 ///
-/// let mut cache = BloomCache::new(4);
+/// let cache = BloomCache::new(4);
 /// // 4 segments imply (4 x 16 = 64) bits (or 8 bytes) per vector.
 ///
 /// let mut vectors = vec![0u8; 8 * 2];
@@ -95,7 +250,7 @@ impl Segment {
 #[derive(Debug)]
 pub struct BloomCache {
     segments: Vec<Segment>,
-    size: usize,
+    size: AtomicUsize,
 }
 
 impl BloomCache {
@@ -103,27 +258,100 @@ impl BloomCache {
     pub fn new(num_segments: usize) -> Self {
         Self {
             segments: (0..num_segments).map(|_| Segment::default()).collect(),
-            size: 0,
+            size: AtomicUsize::new(0),
         }
     }
 
     /// Add given vectors to the cache, marking the correspoding
     /// segments as seen.
-    pub fn add(&mut self, vectors: &[u8]) {
+    ///
+    /// Takes `&self` so a pool of worker threads can populate the cache
+    /// concurrently while indexing a binary dataset into the matching
+    /// `ConcurrentIndex`.
+    pub fn add(&self, vectors: &[u8]) {
         let indices = (0..self.segments.len()).cycle();
         let chunks = vectors.chunks_exact(2);
 
         for (chunk, segment_index) in chunks.zip(indices) {
-            if let Some(segment) = self.segments.get_mut(segment_index) {
+            if let Some(segment) = self.segments.get(segment_index) {
                 let chunk: [u8; 2] = chunk.try_into().unwrap();
                 segment.set(u16::from_be_bytes(chunk));
             }
         }
-        self.size += 1;
+        self.size.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add a representative subset of `vectors` to the cache using
+    /// single-pass reservoir sampling, so that indexing tens of millions
+    /// of vectors doesn't saturate every segment and leave
+    /// `should_reject` unable to prune anything.
+    ///
+    /// `vectors` is a back-to-back sequence of equal-sized binary
+    /// vectors, each `segments.len() * 2` bytes (matching [`Self::add`]).
+    /// Reservoir sampling is used rather than sampling incrementally
+    /// because a discarded vector's bits can't later be retracted from
+    /// a plain bitmap.
+    ///
+    /// Returns the chosen indices (into the chunked `vectors` sequence,
+    /// in ascending order) so callers can cross-check which vectors
+    /// ended up represented in the cache. A smaller `sample_size` trades
+    /// recall of the rejection test for lower saturation.
+    pub fn add_sampled<R: Rng>(
+        &self,
+        vectors: &[u8],
+        sample_size: usize,
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let vector_len = self.segments.len() * 2;
+        if vector_len == 0 || sample_size == 0 {
+            return Vec::new();
+        }
+
+        let num_vectors = vectors.len() / vector_len;
+
+        let mut reservoir: Vec<usize> = Vec::with_capacity(sample_size.min(num_vectors));
+        for i in 0..num_vectors {
+            if i < sample_size {
+                reservoir.push(i);
+            } else {
+                let j = rng.random_range(0..=i);
+                if j < sample_size {
+                    reservoir[j] = i;
+                }
+            }
+        }
+        reservoir.sort_unstable();
+
+        // A single pass over the chunked vectors, applying only the
+        // ones the reservoir selected, so this never holds more than
+        // `sample_size` indices in memory regardless of how many
+        // vectors are given.
+        let mut chosen = reservoir.iter().copied().peekable();
+        for (i, chunk) in vectors.chunks_exact(vector_len).enumerate() {
+            if chosen.peek() != Some(&i) {
+                continue;
+            }
+            chosen.next();
+
+            let segment_indices = (0..self.segments.len()).cycle();
+            for (byte_pair, segment_index) in chunk.chunks_exact(2).zip(segment_indices) {
+                if let Some(segment) = self.segments.get(segment_index) {
+                    let byte_pair: [u8; 2] = byte_pair.try_into().unwrap();
+                    segment.set(u16::from_be_bytes(byte_pair));
+                }
+            }
+        }
+
+        self.size.fetch_add(reservoir.len(), Ordering::Relaxed);
+
+        reservoir
     }
 
     /// Determine whether a range search at the given radius can be safely
     /// rejected (as no hits) for the given query vector.
+    ///
+    /// Takes `&self` so it can be called concurrently with [`BloomCache::add`],
+    /// e.g. while the cache is still being populated by other threads.
     pub fn should_reject(&self, query: &[u8], acceptance_radius: i32) -> bool {
         if acceptance_radius as usize >= self.segments.len() {
             return false;
@@ -146,19 +374,25 @@ impl BloomCache {
     }
 
     /// Reset the cache and clear all indexed state.
+    ///
+    /// Rebuilds `segments` back to its original length rather than
+    /// clearing it, since an empty `segments` makes `should_reject`'s
+    /// `acceptance_radius as usize >= self.segments.len()` guard true
+    /// for every radius, permanently disabling filtering.
     pub fn reset(&mut self) {
-        self.segments.clear();
-        self.size = 0;
+        let num_segments = self.segments.len();
+        self.segments = (0..num_segments).map(|_| Segment::default()).collect();
+        self.size = AtomicUsize::new(0);
     }
 
     /// Get the number of indexed vectors.
     pub fn len(&self) -> usize {
-        self.size
+        self.size.load(Ordering::Relaxed)
     }
 
     /// Determine whether no vectors have been indexed yet.
     pub fn is_empty(&self) -> bool {
-        self.size == 0
+        self.len() == 0
     }
 
     /// Get an iterator over the underlying segments.
@@ -169,16 +403,594 @@ impl BloomCache {
     pub fn iter_segments_mut(&mut self) -> impl Iterator<Item = &mut Segment> {
         self.segments.iter_mut()
     }
+
+    /// Serialize this cache to `writer`, so it can be snapshotted
+    /// alongside a FAISS binary index instead of rebuilt from scratch.
+    ///
+    /// The stream starts with a small header (magic, `num_segments`,
+    /// `size`), followed by one record per segment. Each segment is
+    /// encoded as `Sparse` (a count and sorted set-bit positions) if its
+    /// popcount is below [`SPARSE_POPCOUNT_THRESHOLD`], or `Dense` (the
+    /// raw bitmap) otherwise, and the chosen encoding's payload is then
+    /// compressed with `compression` before being written.
+    pub fn save_to<W: Write>(&self, writer: &mut W, compression: CompressionType) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.segments.len() as u64).to_be_bytes())?;
+        writer.write_all(&(self.len() as u64).to_be_bytes())?;
+
+        for segment in &self.segments {
+            let popcount = segment.popcount();
+            let (encoding, payload) = if popcount < SPARSE_POPCOUNT_THRESHOLD {
+                let positions: Vec<u16> = segment.get_setbits().collect();
+                let mut payload = Vec::with_capacity(2 + positions.len() * 2);
+                payload.extend_from_slice(&(positions.len() as u16).to_be_bytes());
+                for position in positions {
+                    payload.extend_from_slice(&position.to_be_bytes());
+                }
+                (SegmentEncoding::Sparse, payload)
+            } else {
+                (SegmentEncoding::Dense, segment.to_dense_bytes().to_vec())
+            };
+
+            let compressed = compression.compress(&payload);
+
+            writer.write_all(&[encoding.tag(), compression.tag()])?;
+            writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+            writer.write_all(&(compressed.len() as u32).to_be_bytes())?;
+            writer.write_all(&compressed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reconstruct a cache previously written by [`BloomCache::save_to`].
+    pub fn load_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a BloomCache stream (bad magic)",
+            ));
+        }
+
+        let num_segments = read_u64(reader)? as usize;
+        let size = read_u64(reader)? as usize;
+
+        let mut segments = Vec::with_capacity(num_segments);
+        for _ in 0..num_segments {
+            let mut tags = [0u8; 2];
+            reader.read_exact(&mut tags)?;
+            let encoding = SegmentEncoding::from_tag(tags[0])?;
+            let compression = CompressionType::from_tag(tags[1])?;
+
+            let decompressed_len = read_u32(reader)? as usize;
+            let compressed_len = read_u32(reader)? as usize;
+
+            let mut compressed = vec![0u8; compressed_len];
+            reader.read_exact(&mut compressed)?;
+            let payload = compression.decompress(&compressed, decompressed_len)?;
+
+            let segment = match encoding {
+                SegmentEncoding::Dense => {
+                    let bytes: [u8; 8192] = payload.as_slice().try_into().map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidData, "dense segment has wrong length")
+                    })?;
+                    Segment::from_dense_bytes(bytes)
+                }
+                SegmentEncoding::Sparse => {
+                    if payload.len() < 2 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "sparse segment record is too short to contain a count",
+                        ));
+                    }
+                    let count = u16::from_be_bytes(payload[0..2].try_into().unwrap()) as usize;
+                    if payload.len() < 2 + count * 2 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "sparse segment record claims {count} positions but only has {} bytes of payload",
+                                payload.len() - 2
+                            ),
+                        ));
+                    }
+                    let positions: Vec<u16> = payload[2..]
+                        .chunks_exact(2)
+                        .take(count)
+                        .map(|chunk| u16::from_be_bytes(chunk.try_into().unwrap()))
+                        .collect();
+                    Segment::from_sparse_positions(&positions)
+                }
+            };
+            segments.push(segment);
+        }
+
+        Ok(Self {
+            segments,
+            size: AtomicUsize::new(size),
+        })
+    }
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Magic bytes for the on-disk, block-addressed format used by
+/// [`BloomCacheMmap`], distinct from the sequential stream format
+/// written by [`BloomCache::save_to`].
+const MMAP_MAGIC: &[u8; 4] = b"FBMM";
+
+/// Size in bytes of the fixed directory block at file offset 0.
+const DIRECTORY_BLOCK_LEN: usize = 4096;
+
+/// `magic` (4 bytes) + `num_segments` (4 bytes).
+const DIRECTORY_HEADER_LEN: usize = 8;
+
+/// `file_offset` (8 bytes) + `byte_length` (4 bytes) + `encoding_tag`
+/// (1 byte) + 3 bytes padding, for 8-byte alignment of the next entry.
+const DIRECTORY_ENTRY_LEN: usize = 16;
+
+/// Largest number of segments whose directory entries fit in a single
+/// [`DIRECTORY_BLOCK_LEN`]-byte block.
+const MAX_MMAP_SEGMENTS: usize = (DIRECTORY_BLOCK_LEN - DIRECTORY_HEADER_LEN) / DIRECTORY_ENTRY_LEN;
+
+#[derive(Debug, Clone, Copy)]
+struct DirectoryEntry {
+    file_offset: u64,
+    byte_length: u32,
+    encoding: SegmentEncoding,
+}
+
+impl DirectoryEntry {
+    fn write_to(self, out: &mut [u8]) {
+        out[0..8].copy_from_slice(&self.file_offset.to_be_bytes());
+        out[8..12].copy_from_slice(&self.byte_length.to_be_bytes());
+        out[12] = self.encoding.tag();
+        out[13..16].copy_from_slice(&[0, 0, 0]);
+    }
+
+    fn read_from(bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            file_offset: u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            byte_length: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            encoding: SegmentEncoding::from_tag(bytes[12])?,
+        })
+    }
+}
+
+/// A memory-mapped, block-addressed on-disk form of [`BloomCache`], for
+/// caches whose segment count is large enough not to fit comfortably in
+/// RAM.
+///
+/// The file is laid out as a fixed [`DIRECTORY_BLOCK_LEN`]-byte
+/// directory block at offset 0 — mapping each segment index to
+/// `(file_offset, byte_length, encoding_tag)` — followed by the segment
+/// payload blocks themselves. Segments are faulted in lazily through the
+/// memory map, so `get`/`should_reject` read directly from mapped pages
+/// instead of allocating a full 8192-byte segment per touched index.
+#[derive(Debug)]
+pub struct BloomCacheMmap {
+    file: std::fs::File,
+    mmap: memmap2::MmapMut,
+    directory: Vec<DirectoryEntry>,
+}
+
+impl BloomCacheMmap {
+    /// Write a fresh on-disk cache built from `cache`'s current segments,
+    /// using the same sparse/dense encoding choice as
+    /// [`BloomCache::save_to`].
+    pub fn create<P: AsRef<std::path::Path>>(path: P, cache: &BloomCache) -> io::Result<()> {
+        if cache.segments.len() > MAX_MMAP_SEGMENTS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} segments exceed the directory block's capacity of {MAX_MMAP_SEGMENTS}",
+                    cache.segments.len()
+                ),
+            ));
+        }
+
+        let mut directory_block = vec![0u8; DIRECTORY_BLOCK_LEN];
+        directory_block[0..4].copy_from_slice(MMAP_MAGIC);
+        directory_block[4..8].copy_from_slice(&(cache.segments.len() as u32).to_be_bytes());
+
+        let mut payloads = Vec::with_capacity(cache.segments.len());
+        let mut offset = DIRECTORY_BLOCK_LEN as u64;
+        for (index, segment) in cache.segments.iter().enumerate() {
+            let popcount = segment.popcount();
+            let (encoding, payload) = if popcount < SPARSE_POPCOUNT_THRESHOLD {
+                let positions: Vec<u16> = segment.get_setbits().collect();
+                let mut payload = Vec::with_capacity(2 + positions.len() * 2);
+                payload.extend_from_slice(&(positions.len() as u16).to_be_bytes());
+                for position in positions {
+                    payload.extend_from_slice(&position.to_be_bytes());
+                }
+                (SegmentEncoding::Sparse, payload)
+            } else {
+                (SegmentEncoding::Dense, segment.to_dense_bytes().to_vec())
+            };
+
+            let entry = DirectoryEntry {
+                file_offset: offset,
+                byte_length: payload.len() as u32,
+                encoding,
+            };
+            entry.write_to(&mut directory_block[dir_entry_range(index)]);
+            offset += payload.len() as u64;
+            payloads.push(payload);
+        }
+
+        let file = std::fs::File::create(path)?;
+        let mut writer = std::io::BufWriter::new(&file);
+        writer.write_all(&directory_block)?;
+        for payload in payloads {
+            writer.write_all(&payload)?;
+        }
+        writer.flush()
+    }
+
+    /// Open a cache previously written by [`BloomCacheMmap::create`],
+    /// lazily faulting in segments as they're touched.
+    pub fn open_mmap<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+        let mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+
+        if mmap.len() < DIRECTORY_BLOCK_LEN || &mmap[0..4] != MMAP_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a BloomCacheMmap file (bad magic or truncated directory)",
+            ));
+        }
+        let num_segments = u32::from_be_bytes(mmap[4..8].try_into().unwrap()) as usize;
+        if num_segments > MAX_MMAP_SEGMENTS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{num_segments} segments exceed the directory block's capacity of {MAX_MMAP_SEGMENTS}"
+                ),
+            ));
+        }
+
+        let mut directory = Vec::with_capacity(num_segments);
+        for index in 0..num_segments {
+            let entry = DirectoryEntry::read_from(&mmap[dir_entry_range(index)])?;
+            entry
+                .file_offset
+                .checked_add(entry.byte_length as u64)
+                .filter(|&end| end <= mmap.len() as u64)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "segment {index} range {}..+{} exceeds file length {}",
+                            entry.file_offset,
+                            entry.byte_length,
+                            mmap.len()
+                        ),
+                    )
+                })?;
+            directory.push(entry);
+        }
+
+        Ok(Self {
+            file,
+            mmap,
+            directory,
+        })
+    }
+
+    fn segment_bytes(&self, segment_index: usize) -> Option<(&[u8], SegmentEncoding)> {
+        let entry = self.directory.get(segment_index)?;
+        let start = entry.file_offset as usize;
+        let end = start + entry.byte_length as usize;
+        Some((&self.mmap[start..end], entry.encoding))
+    }
+
+    /// Get whether `index` has been seen before in segment
+    /// `segment_index`, reading straight out of the mapped page(s)
+    /// backing that segment.
+    pub fn get(&self, segment_index: usize, index: u16) -> bool {
+        let Some((bytes, encoding)) = self.segment_bytes(segment_index) else {
+            return false;
+        };
+        match encoding {
+            SegmentEncoding::Dense => {
+                let byte_offset = (index / 8) as usize;
+                let bit_offset = 7 - ((index % 8) as usize);
+                bytes[byte_offset] & (1 << bit_offset) != 0
+            }
+            SegmentEncoding::Sparse => {
+                let count = u16::from_be_bytes(bytes[0..2].try_into().unwrap()) as usize;
+                bytes[2..]
+                    .chunks_exact(2)
+                    .take(count)
+                    .any(|chunk| u16::from_be_bytes(chunk.try_into().unwrap()) == index)
+            }
+        }
+    }
+
+    /// Determine whether a range search at the given radius can be
+    /// safely rejected (as no hits) for the given query vector, mirroring
+    /// [`BloomCache::should_reject`].
+    pub fn should_reject(&self, query: &[u8], acceptance_radius: i32) -> bool {
+        if acceptance_radius as usize >= self.directory.len() {
+            return false;
+        }
+
+        let mut misses: i32 = 0;
+        for (chunk, segment_index) in query.chunks_exact(2).zip(0..self.directory.len()) {
+            let chunk: [u8; 2] = chunk.try_into().unwrap();
+            if !self.get(segment_index, u16::from_be_bytes(chunk)) {
+                misses += 1;
+                if misses > acceptance_radius {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Rewrite segment `segment_index`'s on-disk payload from `segment`,
+    /// re-encoding it with the same sparse/dense threshold used by
+    /// [`BloomCacheMmap::create`].
+    ///
+    /// The new payload is always appended to the end of the file rather
+    /// than written in place, since it may not be the same size as
+    /// what's already there. The old bytes become dead space, reclaimed
+    /// by [`BloomCacheMmap::compact`].
+    pub fn update_segment(&mut self, segment_index: usize, segment: &Segment) -> io::Result<()> {
+        if segment_index >= self.directory.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("no such segment: {segment_index}"),
+            ));
+        }
+
+        let popcount = segment.popcount();
+        let (encoding, payload) = if popcount < SPARSE_POPCOUNT_THRESHOLD {
+            let positions: Vec<u16> = segment.get_setbits().collect();
+            let mut payload = Vec::with_capacity(2 + positions.len() * 2);
+            payload.extend_from_slice(&(positions.len() as u16).to_be_bytes());
+            for position in positions {
+                payload.extend_from_slice(&position.to_be_bytes());
+            }
+            (SegmentEncoding::Sparse, payload)
+        } else {
+            (SegmentEncoding::Dense, segment.to_dense_bytes().to_vec())
+        };
+
+        let file_offset = self.file.metadata()?.len();
+        self.file.set_len(file_offset + payload.len() as u64)?;
+        self.mmap = unsafe { memmap2::MmapMut::map_mut(&self.file)? };
+        let start = file_offset as usize;
+        self.mmap[start..start + payload.len()].copy_from_slice(&payload);
+
+        let entry = DirectoryEntry {
+            file_offset,
+            byte_length: payload.len() as u32,
+            encoding,
+        };
+        entry.write_to(&mut self.mmap[dir_entry_range(segment_index)]);
+        self.directory[segment_index] = entry;
+
+        Ok(())
+    }
+
+    /// Number of bytes in the file that no longer belong to any live
+    /// segment payload (e.g. left behind by segment rewrites),
+    /// and would be reclaimed by [`BloomCacheMmap::compact`].
+    pub fn len_on_disk(&self) -> io::Result<u64> {
+        let file_len = self.file.metadata()?.len();
+        let live: u64 = self
+            .directory
+            .iter()
+            .map(|entry| entry.byte_length as u64)
+            .sum();
+        Ok(file_len.saturating_sub(DIRECTORY_BLOCK_LEN as u64 + live))
+    }
+
+    /// Rewrite all live segment payloads contiguously and regenerate the
+    /// directory block, reclaiming any bytes left behind by fragmented
+    /// writes (e.g. from [`BloomCacheMmap::update_segment`]).
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut directory_block = vec![0u8; DIRECTORY_BLOCK_LEN];
+        directory_block[0..4].copy_from_slice(MMAP_MAGIC);
+        directory_block[4..8].copy_from_slice(&(self.directory.len() as u32).to_be_bytes());
+
+        let mut payloads = Vec::with_capacity(self.directory.len());
+        let mut offset = DIRECTORY_BLOCK_LEN as u64;
+        for (index, entry) in self.directory.iter().enumerate() {
+            let start = entry.file_offset as usize;
+            let end = start + entry.byte_length as usize;
+            let payload = self.mmap[start..end].to_vec();
+
+            let new_entry = DirectoryEntry {
+                file_offset: offset,
+                byte_length: entry.byte_length,
+                encoding: entry.encoding,
+            };
+            new_entry.write_to(&mut directory_block[dir_entry_range(index)]);
+            offset += payload.len() as u64;
+            payloads.push(payload);
+        }
+
+        self.file.set_len(0)?;
+        self.file.sync_all()?;
+        {
+            let mut writer = std::io::BufWriter::new(&self.file);
+            writer.seek(std::io::SeekFrom::Start(0))?;
+            writer.write_all(&directory_block)?;
+            for payload in &payloads {
+                writer.write_all(payload)?;
+            }
+            writer.flush()?;
+        }
+
+        self.mmap = unsafe { memmap2::MmapMut::map_mut(&self.file)? };
+        self.directory = (0..payloads.len())
+            .map(|index| DirectoryEntry::read_from(&self.mmap[dir_entry_range(index)]))
+            .collect::<io::Result<_>>()?;
+
+        Ok(())
+    }
+}
+
+fn dir_entry_range(index: usize) -> std::ops::Range<usize> {
+    let start = DIRECTORY_HEADER_LEN + index * DIRECTORY_ENTRY_LEN;
+    start..(start + DIRECTORY_ENTRY_LEN)
+}
+
+/// The subset of a binary index's API that [`BloomFilteredIndex`] needs
+/// in order to keep a [`BloomCache`] in sync with it automatically.
+///
+/// This mirrors the crate's `Index`/`ConcurrentIndex` traits for float
+/// indices, scoped down to what binary, Hamming-distance indexes expose.
+pub trait BinaryIndex {
+    /// The result type returned by `range_search`.
+    type RangeSearchResult: Default;
+
+    /// Vector dimension, in bits.
+    fn d(&self) -> u32;
+
+    /// Add binary vectors (`d() / 8` bytes each) to the index.
+    fn add(&mut self, vectors: &[u8]) -> crate::error::Result<()>;
+
+    /// Search for all vectors within `radius` Hamming distance of `query`.
+    fn range_search(
+        &mut self,
+        query: &[u8],
+        radius: i32,
+    ) -> crate::error::Result<Self::RangeSearchResult>;
+
+    /// Remove all vectors from the index.
+    fn reset(&mut self) -> crate::error::Result<()>;
+}
+
+/// A binary index wrapped with a [`BloomCache`] sized to `d() / 16`
+/// segments, so that narrow-radius `range_search` queries the cache can
+/// prove have no hits are rejected without ever invoking the wrapped
+/// index.
+///
+/// This turns the manual bookkeeping the standalone `BloomCache` needs
+/// (adding to both the index and the cache, and keeping them in sync on
+/// reset) into a single drop-in accelerator.
+#[derive(Debug)]
+pub struct BloomFilteredIndex<I> {
+    index: I,
+    cache: BloomCache,
+    queries_rejected: AtomicUsize,
+    queries_passed: AtomicUsize,
+}
+
+impl<I: BinaryIndex> BloomFilteredIndex<I> {
+    /// Wrap `index` with a cache sized to its dimensionality.
+    pub fn new(index: I) -> Self {
+        let cache = BloomCache::new(index.d() as usize / 16);
+        Self {
+            index,
+            cache,
+            queries_rejected: AtomicUsize::new(0),
+            queries_passed: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add `vectors` to both the wrapped index and the cache.
+    ///
+    /// `vectors` is a batch of N concatenated `d() / 8`-byte vectors, so
+    /// `BloomCache::add` (which counts each call as a single vector in
+    /// `len`) is invoked once per vector, not once for the whole batch.
+    pub fn add(&mut self, vectors: &[u8]) -> crate::error::Result<()> {
+        let vector_len = self.index.d() as usize / 8;
+        if vector_len != 0 {
+            for vector in vectors.chunks_exact(vector_len) {
+                self.cache.add(vector);
+            }
+        }
+        self.index.add(vectors)
+    }
+
+    /// Run `range_search`, first consulting the cache: if it can prove
+    /// the query has no hits within `radius`, an empty result is
+    /// returned without invoking the wrapped index; otherwise the search
+    /// is delegated as normal.
+    pub fn range_search(
+        &mut self,
+        query: &[u8],
+        radius: i32,
+    ) -> crate::error::Result<I::RangeSearchResult> {
+        if self.cache.should_reject(query, radius) {
+            self.queries_rejected.fetch_add(1, Ordering::Relaxed);
+            return Ok(I::RangeSearchResult::default());
+        }
+        self.queries_passed.fetch_add(1, Ordering::Relaxed);
+        self.index.range_search(query, radius)
+    }
+
+    /// Number of queries the cache proved had no hits, so the wrapped
+    /// index was never consulted.
+    pub fn queries_rejected(&self) -> usize {
+        self.queries_rejected.load(Ordering::Relaxed)
+    }
+
+    /// Number of queries forwarded to the wrapped index because the
+    /// cache could not rule them out.
+    pub fn queries_passed(&self) -> usize {
+        self.queries_passed.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of indexed vectors, as tracked by the cache.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Determine whether no vectors have been indexed yet.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Reset both the wrapped index and the cache, and zero the
+    /// rejection counters.
+    ///
+    /// The cache is rebuilt from scratch with `d() / 16` segments, taken
+    /// from the wrapped index after its reset.
+    pub fn reset(&mut self) -> crate::error::Result<()> {
+        self.index.reset()?;
+        self.cache = BloomCache::new(self.index.d() as usize / 16);
+        self.queries_rejected = AtomicUsize::new(0);
+        self.queries_passed = AtomicUsize::new(0);
+        Ok(())
+    }
+
+    /// Borrow the wrapped index.
+    pub fn inner(&self) -> &I {
+        &self.index
+    }
+
+    /// Borrow the underlying cache.
+    pub fn cache(&self) -> &BloomCache {
+        &self.cache
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::{rng, Rng};
+    use rand::rng;
+    use rand::RngCore;
 
     #[test]
     fn segment_ops() {
-        let mut segment = Segment::default();
+        let segment = Segment::default();
         let setbits = segment.get_setbits().collect::<Vec<_>>();
         assert!(setbits.is_empty());
 
@@ -198,7 +1010,7 @@ mod tests {
             .unwrap()
             .into_flat()
             .unwrap();
-        let mut cache = BloomCache::new(index.d() as usize / 16);
+        let cache = BloomCache::new(index.d() as usize / 16);
 
         let num_vectors: usize = 1000;
         let mut data = vec![0u8; (index.d() as usize / 8) * num_vectors];
@@ -225,7 +1037,7 @@ mod tests {
             .unwrap()
             .into_flat()
             .unwrap();
-        let mut cache = BloomCache::new(index.d() as usize / 16);
+        let cache = BloomCache::new(index.d() as usize / 16);
 
         let num_vectors: usize = 1;
         let mut data = vec![0u8; (index.d() as usize / 8) * num_vectors];
@@ -252,4 +1064,311 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn reset_clears_state_without_disabling_future_filtering() {
+        let mut cache = BloomCache::new(4);
+        let far_query = [0xffu8; 8];
+
+        cache.add(&[0u8; 8]);
+        assert!(cache.should_reject(&far_query, 0));
+
+        cache.reset();
+        assert_eq!(cache.len(), 0);
+
+        // A fresh `add`/`should_reject` cycle must behave exactly as it
+        // did before the reset, not unconditionally return `false` (as
+        // it would if `reset` left `segments` empty).
+        cache.add(&[0u8; 8]);
+        assert!(cache.should_reject(&far_query, 0));
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        for compression in [
+            CompressionType::None,
+            CompressionType::Lz4,
+            CompressionType::Miniz,
+        ] {
+            let cache = BloomCache::new(8);
+            let mut rng = rng();
+            let mut vectors = vec![0u8; 16 * 50];
+            rng.fill_bytes(&mut vectors);
+            for vector in vectors.chunks_exact(16) {
+                cache.add(vector);
+            }
+
+            let mut buf = Vec::new();
+            cache.save_to(&mut buf, compression).unwrap();
+
+            let loaded = BloomCache::load_from(&mut buf.as_slice()).unwrap();
+            assert_eq!(loaded.len(), cache.len());
+            for (original, loaded) in cache.iter_segments().zip(loaded.iter_segments()) {
+                assert_eq!(
+                    original.get_setbits().collect::<Vec<_>>(),
+                    loaded.get_setbits().collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn load_from_rejects_truncated_sparse_record() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&1u64.to_be_bytes()); // num_segments
+        buf.extend_from_slice(&0u64.to_be_bytes()); // size
+        buf.push(SegmentEncoding::Sparse.tag());
+        buf.push(CompressionType::None.tag());
+        buf.extend_from_slice(&1u32.to_be_bytes()); // decompressed_len (too short for a count)
+        buf.extend_from_slice(&1u32.to_be_bytes()); // compressed_len
+        buf.push(0u8);
+
+        let err = BloomCache::load_from(&mut buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn load_from_rejects_sparse_record_with_count_exceeding_stored_positions() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&1u64.to_be_bytes()); // num_segments
+        buf.extend_from_slice(&0u64.to_be_bytes()); // size
+        buf.push(SegmentEncoding::Sparse.tag());
+        buf.push(CompressionType::None.tag());
+        // Claims 5 positions but only stores 1 `u16` (4 bytes total).
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&5u16.to_be_bytes());
+        payload.extend_from_slice(&42u16.to_be_bytes());
+        buf.extend_from_slice(&(payload.len() as u32).to_be_bytes()); // decompressed_len
+        buf.extend_from_slice(&(payload.len() as u32).to_be_bytes()); // compressed_len
+        buf.extend_from_slice(&payload);
+
+        let err = BloomCache::load_from(&mut buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn load_from_rejects_mismatched_none_payload_length() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&1u64.to_be_bytes()); // num_segments
+        buf.extend_from_slice(&0u64.to_be_bytes()); // size
+        buf.push(SegmentEncoding::Dense.tag());
+        buf.push(CompressionType::None.tag());
+        buf.extend_from_slice(&8192u32.to_be_bytes()); // decompressed_len
+        buf.extend_from_slice(&4u32.to_be_bytes()); // compressed_len, deliberately wrong
+        buf.extend_from_slice(&[0u8; 4]);
+
+        let err = BloomCache::load_from(&mut buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    fn temp_file_path(name: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("faiss_bloom_cache_mmap_{}_{}", std::process::id(), name));
+        path
+    }
+
+    #[test]
+    fn mmap_cache_matches_in_memory() {
+        let cache = BloomCache::new(8);
+        let mut rng = rng();
+        let mut vectors = vec![0u8; 16 * 50];
+        rng.fill_bytes(&mut vectors);
+        for vector in vectors.chunks_exact(16) {
+            cache.add(vector);
+        }
+
+        let path = temp_file_path("matches");
+        BloomCacheMmap::create(&path, &cache).unwrap();
+        let mmap_cache = BloomCacheMmap::open_mmap(&path).unwrap();
+
+        for query in vectors.chunks_exact(16) {
+            for radius in 0..8 {
+                assert_eq!(
+                    cache.should_reject(query, radius),
+                    mmap_cache.should_reject(query, radius)
+                );
+            }
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_mmap_rejects_truncated_file() {
+        let cache = BloomCache::new(4);
+        cache.add(&[0u8; 8]);
+
+        let path = temp_file_path("truncated");
+        BloomCacheMmap::create(&path, &cache).unwrap();
+
+        // Truncate the file so the last directory entry's range now falls
+        // past the end of the mapped file.
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - 1).unwrap();
+        drop(file);
+
+        let err = BloomCacheMmap::open_mmap(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mmap_cache_compact_reclaims_space() {
+        let cache = BloomCache::new(4);
+        cache.add(&[0u8; 8]);
+
+        let path = temp_file_path("compact");
+        BloomCacheMmap::create(&path, &cache).unwrap();
+        let mut mmap_cache = BloomCacheMmap::open_mmap(&path).unwrap();
+        assert_eq!(mmap_cache.len_on_disk().unwrap(), 0);
+
+        // Rewriting a segment appends a new payload and leaves the old one behind as
+        // dead space, so the file is genuinely fragmented before we
+        // compact it.
+        let replacement = Segment::from_sparse_positions(&[1, 2, 3]);
+        mmap_cache.update_segment(0, &replacement).unwrap();
+        assert!(mmap_cache.len_on_disk().unwrap() > 0);
+
+        let queries: Vec<bool> = (0..4u16)
+            .map(|index| mmap_cache.get(0, index))
+            .collect();
+
+        mmap_cache.compact().unwrap();
+        assert_eq!(mmap_cache.len_on_disk().unwrap(), 0);
+        for (index, expected) in queries.into_iter().enumerate() {
+            assert_eq!(mmap_cache.get(0, index as u16), expected);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// A trivial in-memory binary index, used to exercise
+    /// [`BloomFilteredIndex`]'s pruning logic without a real FAISS index.
+    #[derive(Default)]
+    struct MockBinaryIndex {
+        dimension: u32,
+        vectors: Vec<u8>,
+        range_search_calls: usize,
+    }
+
+    impl MockBinaryIndex {
+        fn new(dimension: u32) -> Self {
+            Self {
+                dimension,
+                ..Default::default()
+            }
+        }
+    }
+
+    impl BinaryIndex for MockBinaryIndex {
+        type RangeSearchResult = Vec<u8>;
+
+        fn d(&self) -> u32 {
+            self.dimension
+        }
+
+        fn add(&mut self, vectors: &[u8]) -> crate::error::Result<()> {
+            self.vectors.extend_from_slice(vectors);
+            Ok(())
+        }
+
+        fn range_search(
+            &mut self,
+            _query: &[u8],
+            _radius: i32,
+        ) -> crate::error::Result<Self::RangeSearchResult> {
+            self.range_search_calls += 1;
+            Ok(self.vectors.clone())
+        }
+
+        fn reset(&mut self) -> crate::error::Result<()> {
+            self.vectors.clear();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bloom_filtered_index_rejects_without_delegating() {
+        let mut index = BloomFilteredIndex::new(MockBinaryIndex::new(64));
+
+        let vector = vec![0xAAu8; 8];
+        index.add(&vector).unwrap();
+        assert_eq!(index.len(), 1);
+
+        // A query that's present shouldn't be rejected, and should reach
+        // the wrapped index.
+        let hits = index.range_search(&vector, 0).unwrap();
+        assert!(!hits.is_empty());
+        assert_eq!(index.queries_passed(), 1);
+        assert_eq!(index.queries_rejected(), 0);
+
+        // A query far from anything indexed should be rejected without
+        // the wrapped index ever seeing it.
+        let far_query = vec![0x55u8; 8];
+        let hits = index.range_search(&far_query, 0).unwrap();
+        assert!(hits.is_empty());
+        assert_eq!(index.inner().range_search_calls, 1);
+        assert_eq!(index.queries_rejected(), 1);
+
+        index.reset().unwrap();
+        assert_eq!(index.len(), 0);
+        assert_eq!(index.queries_rejected(), 0);
+        assert_eq!(index.queries_passed(), 0);
+
+        // The cache must still be able to reject after a reset, not just
+        // report an empty length.
+        assert!(index.cache().should_reject(&far_query, 0));
+    }
+
+    #[test]
+    fn bloom_filtered_index_add_counts_each_vector_in_a_batch() {
+        let mut index = BloomFilteredIndex::new(MockBinaryIndex::new(64));
+
+        let batch: Vec<u8> = (0..3).flat_map(|i| vec![i as u8; 8]).collect();
+        index.add(&batch).unwrap();
+
+        assert_eq!(index.len(), 3);
+    }
+
+    #[test]
+    fn add_sampled_picks_exactly_sample_size_indices() {
+        let cache = BloomCache::new(4);
+        let mut rng = rng();
+
+        let num_vectors: usize = 500;
+        let mut data = vec![0u8; 8 * num_vectors];
+        rng.fill_bytes(&mut data);
+
+        let sample_size = 50;
+        let chosen = cache.add_sampled(&data, sample_size, &mut rng);
+
+        assert_eq!(chosen.len(), sample_size);
+        assert!(chosen.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(chosen.iter().all(|&index| index < num_vectors));
+        assert_eq!(cache.len(), sample_size);
+
+        for &index in &chosen {
+            let vector = &data[index * 8..(index + 1) * 8];
+            assert!(!cache.should_reject(vector, 0));
+        }
+    }
+
+    #[test]
+    fn add_sampled_keeps_all_vectors_when_fewer_than_sample_size() {
+        let cache = BloomCache::new(4);
+        let mut rng = rng();
+
+        let num_vectors: usize = 10;
+        let mut data = vec![0u8; 8 * num_vectors];
+        rng.fill_bytes(&mut data);
+
+        let chosen = cache.add_sampled(&data, 50, &mut rng);
+        assert_eq!(chosen, (0..num_vectors).collect::<Vec<_>>());
+        assert_eq!(cache.len(), num_vectors);
+    }
 }